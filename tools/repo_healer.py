@@ -1,39 +1,478 @@
 #!/usr/bin/env python3
-import argparse, re, json, sys
+import argparse, hashlib, html, io, re, json, mmap, os, socket, subprocess, sys, tarfile, time, urllib.parse, zipfile
 from pathlib import Path
 
-CONFLICT_RE = re.compile(r'^<<<<<<<|^=======|^>>>>>>>', re.M)
+# exact markers only: 7-char runs, so setext underlines and longer rules don't count;
+# git labels <<<<<<<, ||||||| and >>>>>>> (e.g. '||||||| base') but always writes ======= bare
+CONFLICT_RE = re.compile(rb'^(?:<{7}|\|{7}|>{7})(?= |\r?$)|^={7}(?=\r?$)', re.M)
+ARCHIVE_SUFFIXES = ('.zip', '.tar.gz', '.tgz')
+MAX_MEMBER_BYTES = 64 * 1024 * 1024  # archive members above this are reported, not decompressed
+# loose files at or above this are mmapped instead of read; see read_file for the truncation risk
+MMAP_MIN_BYTES = 16 * 1024 * 1024
+LOCK_PATH = Path('build/.healer.lock')
+REPORTS_DIR = Path('build/reports')
+AUDIT_PATH = REPORTS_DIR / 'audit-bootstrap.md'
+METRICS_PATH = REPORTS_DIR / 'metrics.json'
+HTML_PATH = REPORTS_DIR / 'audit.html'
+JSON_PATH = REPORTS_DIR / 'audit.json'
+CACHE_PATH = Path('build/cache/scan.json')
+CACHE_VERSION = 6  # bump whenever scan rules change so stale hit lists are dropped
+COUNT_KEYS = ('conflict_markers', 'malformed_blocks', 'unreadable_files')
+# count key -> (threshold flag, exit code); 1 stays the plain --check/--strict failure
+THRESHOLDS = {'conflict_markers': ('max_markers', 3), 'malformed_blocks': ('max_malformed', 4), 'unreadable_files': ('max_unreadable', 5)}
+LOCK_HELD_EXIT = 6  # another run holds build/ and --wait was not given; nothing was scanned
 
-def scan_conflict_markers(root: Path):
-    hits = []
-    for p in root.rglob('*'):
-        if not p.is_file():
+MESSAGES = {
+    'en': {
+        'title': 'Repo Healer quick check',
+        'conflict_markers': 'conflict markers',
+        'unreadable_files': 'unreadable files',
+        'markers_heading': 'markers',
+        'malformed_blocks': 'malformed conflict blocks',
+        'malformed_line': '{path} bytes {start}-{end}: {kind}',
+        'kind_nested': 'conflict opened inside another conflict',
+        'kind_unterminated': '<<<<<<< never closed by >>>>>>>',
+        'kind_stray': '>>>>>>> without a matching <<<<<<<',
+        'line': 'line',
+        'hook_markers': '{path}: {count} conflict marker(s), first at line {line}',
+        'hook_unreadable': '{path}: unreadable ({msg})',
+        'member_too_large': 'archive member larger than {limit} bytes; not scanned',
+        'lock_held': 'another healer run holds {path}; re-run with --wait to queue behind it',
+        'reports_unchanged': 'reports unchanged; nothing to commit',
+        'commit_mid_merge': 'a merge is in progress; not committing reports (git refuses partial commits mid-merge)',
+        'commit_failed': 'could not commit reports: {err}',
+        'statsd_failed': 'could not push metrics to statsd {addr}: {err}',
+        'threshold_exceeded': '{label}: {count} exceeds --{flag} {limit} (exit {code})',
+    },
+    'es': {
+        'title': 'Revisión rápida de Repo Healer',
+        'conflict_markers': 'marcadores de conflicto',
+        'unreadable_files': 'archivos ilegibles',
+        'markers_heading': 'marcadores',
+        'malformed_blocks': 'bloques de conflicto malformados',
+        'malformed_line': '{path} bytes {start}-{end}: {kind}',
+        'kind_nested': 'conflicto abierto dentro de otro conflicto',
+        'kind_unterminated': '<<<<<<< nunca cerrado con >>>>>>>',
+        'kind_stray': '>>>>>>> sin su <<<<<<< correspondiente',
+        'line': 'línea',
+        'hook_markers': '{path}: {count} marcador(es) de conflicto, el primero en la línea {line}',
+        'hook_unreadable': '{path}: ilegible ({msg})',
+        'member_too_large': 'miembro del archivo de más de {limit} bytes; no se revisó',
+        'lock_held': 'otra ejecución del healer tiene {path}; vuelve a ejecutar con --wait para esperar su turno',
+        'reports_unchanged': 'los informes no cambiaron; no hay nada que confirmar',
+        'commit_mid_merge': 'hay una fusión en curso; no se confirman los informes (git rechaza commits parciales durante una fusión)',
+        'commit_failed': 'no se pudieron confirmar los informes: {err}',
+        'statsd_failed': 'no se pudieron enviar las métricas a statsd {addr}: {err}',
+        'threshold_exceeded': '{label}: {count} supera --{flag} {limit} (salida {code})',
+    },
+}
+LANG = 'en'
+
+def t(key, **kw):
+    return MESSAGES[LANG].get(key, MESSAGES['en'][key]).format(**kw)
+
+def is_archive(p: Path):
+    return p.name.lower().endswith(ARCHIVE_SUFFIXES)
+
+def read_member(fh, label, errors):
+    # read one byte past the cap so a lying zip header can't smuggle in an oversized member
+    data = fh.read(MAX_MEMBER_BYTES + 1)
+    if len(data) > MAX_MEMBER_BYTES:
+        errors.append((label, t('member_too_large', limit=MAX_MEMBER_BYTES)))
+        return None
+    return data
+
+def iter_archive(p: Path, fileobj=None, errors=None):
+    # members are streamed one at a time and labelled archive!member so hits point inside the batch
+    errors = errors if errors is not None else []
+    if p.name.lower().endswith('.zip'):
+        with zipfile.ZipFile(fileobj or p) as zf:
+            for info in zf.infolist():
+                if info.is_dir():
+                    continue
+                label = f'{p}!{info.filename}'
+                if info.file_size > MAX_MEMBER_BYTES:
+                    errors.append((label, t('member_too_large', limit=MAX_MEMBER_BYTES)))
+                    continue
+                with zf.open(info) as member_fh:
+                    data = read_member(member_fh, label, errors)
+                if data is not None:
+                    yield label, data
+    else:
+        with tarfile.open(p, 'r:gz', fileobj=fileobj) as tf:
+            for member in tf:
+                if not member.isfile():
+                    continue
+                label = f'{p}!{member.name}'
+                if member.size > MAX_MEMBER_BYTES:
+                    errors.append((label, t('member_too_large', limit=MAX_MEMBER_BYTES)))
+                    continue
+                member_fh = tf.extractfile(member)
+                if member_fh is not None:
+                    data = read_member(member_fh, label, errors)
+                    if data is not None:
+                        yield label, data
+
+def read_file(p: Path):
+    # content files are small, so a plain read is the default. Only very large files are mmapped:
+    # if one shrinks while mapped (in-place rewrite, checkout mid-scan), touching the lost pages
+    # raises SIGBUS and kills the process outright instead of surfacing as an unreadable file
+    with open(p, 'rb') as fh:
+        if os.fstat(fh.fileno()).st_size >= MMAP_MIN_BYTES:
+            try:
+                return mmap.mmap(fh.fileno(), 0, access=mmap.ACCESS_READ)
+            except (ValueError, OSError):
+                pass
+        return fh.read()
+
+def archive_arg(value):
+    if not is_archive(Path(value)):
+        raise argparse.ArgumentTypeError(f'expected a {"/".join(ARCHIVE_SUFFIXES)} archive, got {value!r}')
+    return value
+
+def source_paths(root: Path, archives=()):
+    # an --archive that already sits under content/ must not be scanned (and counted) twice
+    paths, seen = [], set()
+    for p in [p for p in sorted(root.rglob('*')) if p.is_file()] + [Path(a) for a in archives]:
+        key = p.resolve()
+        if key not in seen:
+            seen.add(key)
+            paths.append(p)
+    return paths
+
+def load_cache(path: Path):
+    try:
+        data = json.loads(path.read_text(encoding='utf-8'))
+    except (OSError, ValueError):
+        return {}
+    return data.get('files', {}) if data.get('version') == CACHE_VERSION else {}
+
+def save_cache(path: Path, files):
+    path.parent.mkdir(parents=True, exist_ok=True)
+    path.write_text(json.dumps({'version': CACHE_VERSION, 'files': files}), encoding='utf-8')
+
+def bump(stats, key, n=1):
+    if stats is not None:
+        stats[key] = stats.get(key, 0) + n
+
+def scan_archive(p: Path, stats=None):
+    # hashed and scanned in the same pass so only one member is held in memory at a time
+    h, hits, bad, skipped = hashlib.blake2b(digest_size=16), [], [], []
+    for label, buf in iter_archive(p, errors=skipped):
+        h.update(label.encode('utf-8')); h.update(buf)
+        scan_buffer(label, buf, hits, bad)
+    return h.hexdigest(), hits, bad, skipped
+
+def scan_loose(p: Path, cached, stats=None):
+    buf = read_file(p)
+    try:
+        h = hashlib.blake2b(digest_size=16)
+        h.update(str(p).encode('utf-8')); h.update(buf)
+        digest = h.hexdigest()
+        if cached and cached['hash'] == digest:
+            bump(stats, 'cache_hits')
+            return digest, [tuple(x) for x in cached['hits']], [tuple(b) for b in cached['malformed']]
+        bump(stats, 'cache_misses')
+        hits, bad = [], []
+        scan_buffer(str(p), buf, hits, bad)
+        return digest, hits, bad
+    finally:
+        if isinstance(buf, mmap.mmap):
+            buf.close()
+
+def scan_path(p: Path, stats=None, cache=None, malformed=None, errors=None):
+    # mtime+ctime+size is the cheap pre-check (ctime catches chmod, which can make a file unreadable);
+    # the content hash catches touched-but-unchanged files
+    st = p.stat()
+    cached = cache.get(str(p)) if cache is not None else None
+    stamp = {'mtime_ns': st.st_mtime_ns, 'ctime_ns': st.st_ctime_ns, 'size': st.st_size}
+    # coverage counts every file whether or not the cache answered; sources_rescanned counts real reads
+    bump(stats, 'sources_scanned'); bump(stats, 'bytes_scanned', st.st_size)
+    if cached and all(cached[k] == v for k, v in stamp.items()):
+        bump(stats, 'cache_hits')
+        hits, bad, skipped = ([tuple(x) for x in cached[k]] for k in ('hits', 'malformed', 'skipped'))
+    else:
+        bump(stats, 'sources_rescanned')
+        if is_archive(p):
+            bump(stats, 'cache_misses')
+            digest, hits, bad, skipped = scan_archive(p, stats)
+        else:
+            (digest, hits, bad), skipped = scan_loose(p, cached, stats), []
+        if cache is not None:
+            cache[str(p)] = {**stamp, 'hash': digest, 'hits': hits, 'malformed': bad, 'skipped': skipped}
+    if malformed is not None:
+        malformed += bad
+    if errors is not None:
+        errors += skipped
+    return hits
+
+def scan_buffer(label, buf, hits, malformed=None):
+    # works on bytes or mmap alike; lines are counted incrementally and only up to each hit
+    line, pos, opened = 1, 0, []
+    for m in CONFLICT_RE.finditer(buf):
+        nl = buf.find(b'\n', pos, m.start())
+        while nl != -1:
+            line += 1
+            nl = buf.find(b'\n', nl + 1, m.start())
+        pos = m.start()
+        tok = m.group(0).decode('ascii')
+        # ======= and ||||||| are ordinary text (e.g. a markdown underline) unless a block is open
+        if tok in ('=======', '|||||||') and not opened:
             continue
+        hits.append((label, line, tok))
+        # track block structure so nested, unterminated and stray markers get a byte range in the audit
+        if tok == '<<<<<<<':
+            if opened and malformed is not None:
+                malformed.append((label, opened[-1], m.end(), 'nested'))
+            opened.append(m.start())
+        elif tok == '>>>>>>>':
+            if opened:
+                opened.pop()
+            elif malformed is not None:
+                malformed.append((label, m.start(), m.end(), 'stray'))
+    # no salvage step: the healer never parses or rewrites content, so malformed blocks are
+    # reported with byte ranges for a human to fix rather than partially recovered
+    if malformed is not None:
+        malformed += [(label, start, len(buf), 'unterminated') for start in opened]
+
+def scan_conflict_markers(root: Path, archives=(), errors=None, stats=None, cache=None, malformed=None):
+    hits = []
+    paths = source_paths(root, archives)
+    for p in paths:
         try:
-            text = p.read_text(encoding='utf-8', errors='ignore')
-        except Exception:
-            continue
-        for m in CONFLICT_RE.finditer(text):
-            line = text.count('\n', 0, m.start()) + 1
-            hits.append((str(p), line, m.group(0).strip()))
+            hits += scan_path(p, stats, cache, malformed, errors)
+        except Exception as e:
+            # one unreadable or vanished file is reported, not fatal
+            if errors is not None:
+                errors.append((str(p), f'{type(e).__name__}: {e}'))
+    if cache is not None:
+        # forget files that disappeared since the last run
+        for key in set(cache) - {str(p) for p in paths}:
+            del cache[key]
     return hits
 
+def host_port(value):
+    host, sep, port = value.rpartition(':')
+    if not sep or not port.isdigit() or not 0 < int(port) < 65536:
+        raise argparse.ArgumentTypeError(f'expected HOST:PORT, got {value!r}')
+    return host or 'localhost', int(port)
+
+def push_statsd(addr, metrics):
+    # metrics are best-effort; an unreachable daemon must not swallow the run's exit code
+    lines = [f'repo_healer.{k}:{v}|g' for k, v in metrics.items()]
+    try:
+        with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as sock:
+            sock.sendto('\n'.join(lines).encode('ascii'), addr)
+    except OSError as e:
+        print(t('statsd_failed', addr=f'{addr[0]}:{addr[1]}', err=e), file=sys.stderr)
+
+def iter_staged(root: Path, errors):
+    # hook mode reads the index, not the working tree, so unstaged edits can't mask a bad commit
+    names = subprocess.run(['git', 'diff', '--cached', '--name-only', '-z', '--diff-filter=ACMR', '--', str(root)],
+                           capture_output=True, check=True).stdout.split(b'\0')
+    for name in filter(None, names):
+        p = Path(os.fsdecode(name))
+        try:
+            data = subprocess.run(['git', 'show', f':{p.as_posix()}'], capture_output=True, check=True).stdout
+            yield from (iter_archive(p, io.BytesIO(data), errors) if is_archive(p) else [(str(p), data)])
+        except Exception as e:
+            errors.append((str(p), f'{type(e).__name__}: {e}'))
+
+def run_hook(root: Path):
+    errors, hits = [], []
+    for label, buf in iter_staged(root, errors):
+        scan_buffer(label, buf, hits)
+    by_file = {}
+    for p, ln, _ in hits:
+        by_file.setdefault(p, []).append(ln)
+    for p, lines in by_file.items():
+        print(t('hook_markers', path=p, count=len(lines), line=lines[0]), file=sys.stderr)
+    for p, msg in errors:
+        print(t('hook_unreadable', path=p, msg=msg), file=sys.stderr)
+    return 1 if hits or errors else 0
+
+def try_lock(fd):
+    # imported lazily so platforms without fcntl (Windows) can still run the lock-free modes
+    if os.name == 'nt':
+        import msvcrt
+        os.lseek(fd, 0, os.SEEK_SET)
+        try:
+            msvcrt.locking(fd, msvcrt.LK_NBLCK, 1)
+            return True
+        except OSError:
+            return False
+    import fcntl
+    try:
+        fcntl.flock(fd, fcntl.LOCK_EX | fcntl.LOCK_NB)
+        return True
+    except BlockingIOError:
+        return False
+
+def unlock(fd):
+    if os.name == 'nt':
+        import msvcrt
+        os.lseek(fd, 0, os.SEEK_SET)
+        msvcrt.locking(fd, msvcrt.LK_UNLCK, 1)
+    else:
+        import fcntl
+        fcntl.flock(fd, fcntl.LOCK_UN)
+
+def acquire_lock(path: Path, wait: bool):
+    # the OS drops the lock when its holder exits, so a crashed run never leaves a stale lock behind
+    path.parent.mkdir(parents=True, exist_ok=True)
+    fd = os.open(path, os.O_CREAT | os.O_RDWR)
+    while not try_lock(fd):
+        if not wait:
+            os.close(fd)
+            print(t('lock_held', path=path), file=sys.stderr)
+            sys.exit(LOCK_HELD_EXIT)
+        time.sleep(1)
+    return fd
+
+def release_lock(fd):
+    # the file itself stays; unlinking it would let a waiter lock an inode the next run can't see
+    unlock(fd)
+    os.close(fd)
+
+def source_link(label):
+    # archive members link to the archive itself; hrefs are relative so the report works from a checkout
+    path = label.split('!', 1)[0]
+    href = html.escape(urllib.parse.quote(os.path.relpath(path, REPORTS_DIR).replace(os.sep, '/')), quote=True)
+    return f'<a href="{href}">{html.escape(label)}</a>'
+
+def render_html(counts, hits, malformed, errors):
+    esc = html.escape
+    by_file = {}
+    for p, ln, tok in hits:
+        by_file.setdefault(p, []).append((ln, tok))
+    body = [f"<h1>{esc(t('title'))}</h1>", '<ul>'] + [f'<li>{esc(t(k))}: <b>{n}</b></li>' for k, n in counts.items()] + ['</ul>']
+    if by_file:
+        body.append(f"<h2>{esc(t('markers_heading'))}</h2>")
+        for p, marks in by_file.items():
+            items = ''.join(f"<li>{esc(t('line'))} {ln}: <code>{esc(tok)}</code></li>" for ln, tok in marks)
+            body.append(f'<details><summary>{source_link(p)} ({len(marks)})</summary><ul>{items}</ul></details>')
+    if malformed:
+        items = ''.join(f"<li>{source_link(p)} bytes {a}-{b}: {esc(t(f'kind_{k}'))}</li>" for p, a, b, k in malformed)
+        body.append(f"<details open><summary>{esc(t('malformed_blocks'))} ({len(malformed)})</summary><ul>{items}</ul></details>")
+    if errors:
+        items = ''.join(f'<li>{source_link(p)}: {esc(msg)}</li>' for p, msg in errors)
+        body.append(f"<details open><summary>{esc(t('unreadable_files'))} ({len(errors)})</summary><ul>{items}</ul></details>")
+    return (f'<!doctype html>\n<html lang="{LANG}"><head><meta charset="utf-8"><title>{esc(t("title"))}</title>'
+            '<style>body{font-family:sans-serif;max-width:60em;margin:2em auto}summary{cursor:pointer}code{color:#b00}</style>'
+            '</head><body>\n' + '\n'.join(body) + '\n</body></html>\n')
+
+def audit_json(counts, hits, malformed, errors):
+    # field names are a contract for CI scripts; keep them stable and untranslated
+    return {
+        'counts': counts,
+        'markers': [{'path': p, 'line': ln, 'marker': tok} for p, ln, tok in hits],
+        'malformed': [{'path': p, 'start': a, 'end': b, 'kind': k} for p, a, b, k in malformed],
+        'unreadable': [{'path': p, 'error': msg} for p, msg in errors],
+    }
+
+def committed_counts():
+    # deltas are against the last committed report, not whatever an earlier uncommitted run left on disk
+    shown = subprocess.run(['git', 'show', f'HEAD:{JSON_PATH.as_posix()}'], capture_output=True)
+    if shown.returncode != 0:
+        return {}
+    try:
+        return json.loads(shown.stdout)['counts']
+    except (ValueError, KeyError):
+        return {}
+
+def commit_reports(before, after):
+    # the healer typically runs mid-merge, where `git commit -- <paths>` is refused outright
+    if subprocess.run(['git', 'rev-parse', '-q', '--verify', 'MERGE_HEAD'], capture_output=True).returncode == 0:
+        print(t('commit_mid_merge'), file=sys.stderr)
+        return
+    try:
+        stage_and_commit(before, after)
+    except subprocess.CalledProcessError as e:
+        print(t('commit_failed', err=(e.stderr or b'').decode('utf-8', 'replace').strip() or e), file=sys.stderr)
+
+def stage_and_commit(before, after):
+    # metrics carry run durations, so committing them would make every run look like a change
+    paths = [str(REPORTS_DIR), f':(exclude){METRICS_PATH}']
+    subprocess.run(['git', 'add', '--', *paths], check=True, capture_output=True)
+    if subprocess.run(['git', 'diff', '--cached', '--quiet', '--', *paths]).returncode == 0:
+        print(t('reports_unchanged'))
+        return
+    label = MESSAGES['en']
+    deltas = [f'- {label[k]}: {before.get(k, 0)} -> {n}' for k, n in after.items()]
+    changed = [f'{label[k]} {before.get(k, 0)}->{n}' for k, n in after.items() if before.get(k, 0) != n]
+    subject = 'healer: refresh audit' + (f" ({', '.join(changed)})" if changed else '')
+    subprocess.run(['git', 'commit', '-q', '-m', subject, '-m', '\n'.join(deltas), '--', *paths], check=True, capture_output=True)
+
+def exceeded_thresholds(counts, args):
+    codes = []
+    for key, (flag, code) in THRESHOLDS.items():
+        limit = getattr(args, flag)
+        if limit is not None and counts[key] > limit:
+            print(t('threshold_exceeded', label=t(key), count=counts[key], flag=flag.replace('_', '-'), limit=limit, code=code), file=sys.stderr)
+            codes.append(code)
+    return codes
+
 if __name__ == '__main__':
     ap = argparse.ArgumentParser(description='Repo Healer quick check — scans for conflict markers')
     ap.add_argument('--check', action='store_true')
     ap.add_argument('--strict', action='store_true')
+    ap.add_argument('--archive', type=archive_arg, action='append', default=[], help='extra .zip/.tar.gz batch to scan in memory (repeatable)')
+    ap.add_argument('--wait', action='store_true', help='wait for a concurrent run to release build/ instead of failing fast (exit 6)')
+    ap.add_argument('--commit', action='store_true', help='commit build/reports/ with a summary of the audit deltas')
+    ap.add_argument('--statsd', type=host_port, metavar='HOST:PORT', help='also push metrics.json gauges to a statsd daemon')
+    ap.add_argument('--staged', action='store_true', help='pre-commit hook mode: check staged files under content/ only, write no reports')
+    ap.add_argument('--no-cache', action='store_true', help='ignore build/cache/ and rescan every file')
+    ap.add_argument('--max-markers', type=int, metavar='N', help='exit 3 when more than N conflict markers are found, even without --check')
+    ap.add_argument('--max-malformed', type=int, metavar='N', help='exit 4 when more than N malformed conflict blocks are found')
+    ap.add_argument('--max-unreadable', type=int, metavar='N', help='exit 5 when more than N files cannot be read')
+    ap.add_argument('--lang', choices=sorted(MESSAGES), default='en', help='language for the summary, audit headings and errors')
     args = ap.parse_args()
+    LANG = args.lang
+
+    if args.staged:
+        sys.exit(run_hook(Path('content')))
 
-    root = Path('content'); root.mkdir(parents=True, exist_ok=True)
-    hits = scan_conflict_markers(root)
+    lock_fd = acquire_lock(LOCK_PATH, args.wait)
+    try:
+        root = Path('content'); root.mkdir(parents=True, exist_ok=True)
+        started = time.monotonic()
+        errors, malformed, stats = [], [], {}
+        cache = {} if args.no_cache else load_cache(CACHE_PATH)
+        hits = scan_conflict_markers(root, args.archive, errors, stats, cache, malformed)
+        save_cache(CACHE_PATH, cache)
 
-    out = ['Repo Healer quick check', f'- conflict markers: {len(hits)}']
-    if hits:
-        out.append('## markers')
-        out += [f"- {p}:{ln} {tok}" for p,ln,tok in hits[:200]]
-    Path('build/reports').mkdir(parents=True, exist_ok=True)
-    Path('build/reports/audit-bootstrap.md').write_text('\n'.join(out), encoding='utf-8')
-    print('\n'.join(out))
+        counts = {'conflict_markers': len(hits), 'malformed_blocks': len(malformed), 'unreadable_files': len(errors)}
+        out = [t('title')] + [f'- {t(k)}: {n}' for k, n in counts.items()]
+        if hits:
+            out.append(f"## {t('markers_heading')}")
+            out += [f"- {p}:{ln} {tok}" for p,ln,tok in hits[:200]]
+        if malformed:
+            out.append(f"## {t('malformed_blocks')}")
+            out += ['- ' + t('malformed_line', path=p, start=a, end=b, kind=t(f'kind_{k}')) for p,a,b,k in malformed]
+        if errors:
+            out.append(f"## {t('unreadable_files')}")
+            out += [f"- {p}: {msg}" for p,msg in errors]
+        REPORTS_DIR.mkdir(parents=True, exist_ok=True)
+        AUDIT_PATH.write_text('\n'.join(out), encoding='utf-8')
+        HTML_PATH.write_text(render_html(counts, hits, malformed, errors), encoding='utf-8')
+        JSON_PATH.write_text(json.dumps(audit_json(counts, hits, malformed, errors), indent=2, ensure_ascii=False) + '\n', encoding='utf-8')
+        print('\n'.join(out))
+        metrics = {'sources_scanned': stats.get('sources_scanned', 0), 'bytes_scanned': stats.get('bytes_scanned', 0),
+                   'sources_rescanned': stats.get('sources_rescanned', 0),
+                   'conflict_markers': len(hits), 'files_with_markers': len({p for p, _, _ in hits}),
+                   'malformed_blocks': len(malformed), 'unreadable_files': len(errors),
+                   'cache_hit_rate': round(stats.get('cache_hits', 0) / max(1, stats.get('cache_hits', 0) + stats.get('cache_misses', 0)), 3),
+                   'duration_ms': round((time.monotonic() - started) * 1000)}
+        METRICS_PATH.write_text(json.dumps(metrics, indent=2) + '\n', encoding='utf-8')
+        if args.statsd:
+            push_statsd(args.statsd, metrics)
+        if args.commit:
+            commit_reports(committed_counts(), counts)
+    finally:
+        release_lock(lock_fd)
 
+    codes = exceeded_thresholds(counts, args)
+    if codes:
+        sys.exit(codes[0])
     if hits and (args.check or args.strict):
         sys.exit(1)
@@ -0,0 +1,126 @@
+#!/usr/bin/env python3
+"""Scanner and cache tests for repo_healer; run with python -m unittest discover -s tools"""
+
+import os, sys, tempfile, time, unittest
+from pathlib import Path
+from unittest import mock
+
+sys.path.insert(0, str(Path(__file__).resolve().parent))
+import repo_healer  # noqa: E402
+
+
+class ScanBufferTest(unittest.TestCase):
+    def scan(self, data):
+        hits, malformed = [], []
+        repo_healer.scan_buffer('f', data, hits, malformed)
+        return [(ln, tok) for _, ln, tok in hits], [(a, b, kind) for _, a, b, kind in malformed]
+
+    def test_only_exact_seven_char_markers_match(self):
+        self.assertEqual(self.scan(b'<<<<<<<< x\n========\n>>>>>>>>\n<<<<<<<x\n'), ([], []))
+
+    def test_bare_separator_outside_block_is_ignored(self):
+        self.assertEqual(self.scan(b'Title\n=======\n\nMore\n|||||||\n'), ([], []))
+
+    def test_labelled_base_marker_inside_block(self):
+        hits, malformed = self.scan(b'<<<<<<< ours\nx\n||||||| base\nw\n=======\ny\n>>>>>>> theirs\n')
+        self.assertEqual(hits, [(1, '<<<<<<<'), (3, '|||||||'), (5, '======='), (7, '>>>>>>>')])
+        self.assertEqual(malformed, [])
+
+    def test_crlf_markers(self):
+        hits, malformed = self.scan(b'<<<<<<< a\r\n=======\r\n>>>>>>> b\r\n')
+        self.assertEqual(hits, [(1, '<<<<<<<'), (2, '======='), (3, '>>>>>>>')])
+        self.assertEqual(malformed, [])
+
+    def test_nested_block_range(self):
+        data = b'<<<<<<< a\n<<<<<<< b\n=======\n>>>>>>> c\n=======\n>>>>>>> d\n'
+        self.assertEqual(self.scan(data)[1], [(0, 17, 'nested')])
+
+    def test_unterminated_block_range(self):
+        data = b'ok\n<<<<<<< a\nzz\n'
+        self.assertEqual(self.scan(data)[1], [(3, len(data), 'unterminated')])
+
+    def test_stray_closer_range(self):
+        self.assertEqual(self.scan(b'x\n>>>>>>> b\n'), ([(2, '>>>>>>>')], [(2, 9, 'stray')]))
+
+
+class ScanCacheTest(unittest.TestCase):
+    def setUp(self):
+        self.tmp = tempfile.TemporaryDirectory()
+        self.root = Path(self.tmp.name) / 'content'
+        self.root.mkdir()
+        self.marked = self.root / 'marked.json'
+        self.clean = self.root / 'clean.json'
+        self.marked.write_text('{}\n<<<<<<< ours\n=======\n>>>>>>> theirs\n', encoding='utf-8')
+        self.clean.write_text('{}\n', encoding='utf-8')
+        self.cache = {}
+        self.scan()
+
+    def tearDown(self):
+        self.tmp.cleanup()
+
+    def scan(self):
+        self.stats, self.errors = {}, []
+        return repo_healer.scan_conflict_markers(self.root, errors=self.errors, stats=self.stats, cache=self.cache)
+
+    def bump_times(self, path):
+        later = time.time_ns() + 5_000_000_000
+        os.utime(path, ns=(later, later))
+
+    def test_unchanged_rerun_skips_reading(self):
+        with mock.patch.object(repo_healer, 'read_file', wraps=repo_healer.read_file) as mapped:
+            hits = self.scan()
+        mapped.assert_not_called()
+        self.assertEqual(self.stats.get('cache_hits'), 2)
+        self.assertEqual(len(hits), 3)
+
+    def test_warm_cache_still_reports_coverage(self):
+        self.scan()
+        self.assertEqual(self.stats['sources_scanned'], 2)
+        self.assertEqual(self.stats['bytes_scanned'], self.marked.stat().st_size + self.clean.stat().st_size)
+        self.assertNotIn('sources_rescanned', self.stats)
+
+    def test_edit_rescans_file(self):
+        self.marked.write_text('{}\n', encoding='utf-8')
+        self.bump_times(self.marked)
+        hits = self.scan()
+        self.assertEqual(hits, [])
+        self.assertEqual(self.stats.get('cache_misses'), 1)
+        self.assertEqual(self.stats.get('sources_rescanned'), 1)
+        self.assertEqual(self.cache[str(self.marked)]['hits'], [])
+
+    def test_touch_only_reuses_hits_by_hash(self):
+        self.bump_times(self.marked)
+        hits = self.scan()
+        self.assertEqual(len(hits), 3)
+        self.assertEqual(self.stats.get('sources_rescanned'), 1)
+        self.assertEqual(self.stats.get('cache_hits'), 2)
+        self.assertEqual(self.cache[str(self.marked)]['mtime_ns'], self.marked.stat().st_mtime_ns)
+
+    def test_delete_prunes_entry(self):
+        self.clean.unlink()
+        self.scan()
+        self.assertNotIn(str(self.clean), self.cache)
+        self.assertIn(str(self.marked), self.cache)
+
+    def test_version_bump_drops_cache(self):
+        path = Path(self.tmp.name) / 'scan.json'
+        repo_healer.save_cache(path, self.cache)
+        self.assertEqual(set(repo_healer.load_cache(path)), set(self.cache))
+        with mock.patch.object(repo_healer, 'CACHE_VERSION', repo_healer.CACHE_VERSION + 1):
+            self.assertEqual(repo_healer.load_cache(path), {})
+
+    def test_chmod_bypasses_fast_path(self):
+        time.sleep(0.01)
+        os.chmod(self.marked, 0)
+        try:
+            with mock.patch.object(repo_healer, 'read_file', wraps=repo_healer.read_file) as mapped:
+                self.scan()
+            mapped.assert_called_once_with(self.marked)
+            if os.geteuid() != 0:  # root can still read mode-000 files
+                self.assertEqual([p for p, _ in self.errors], [str(self.marked)])
+        finally:
+            os.chmod(self.marked, 0o644)
+
+
+if __name__ == '__main__':
+    unittest.main()